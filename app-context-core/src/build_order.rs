@@ -0,0 +1,116 @@
+use std::collections::VecDeque;
+
+use crate::error::{AppContextError, AppContextResult};
+use crate::{BaseInfo, ObjDynConstructor};
+
+/// resolves the order in which `constructors` must be built so that every declared
+/// `ObjectMeta::deps` is already built by the time its dependent runs, using Kahn's algorithm.
+pub(crate) fn resolve_build_order(
+    constructors: &[&ObjDynConstructor],
+) -> AppContextResult<Vec<usize>> {
+    let n = constructors.len();
+    // edges[provider] = dependents that need `provider` built first
+    let mut edges: Vec<Vec<usize>> = vec![Vec::new(); n];
+    let mut indegree = vec![0usize; n];
+
+    for (dependent, constructor) in constructors.iter().enumerate() {
+        for dep in constructor.meta().deps() {
+            let provider = constructors
+                .iter()
+                .position(|candidate| candidate.meta().compat_with(*dep));
+            let Some(provider) = provider else {
+                let not_found = AppContextError::object_not_found(dep.name(), dep.type_name());
+                return Err(AppContextError::resolution(
+                    *constructor.meta().type_info(),
+                    not_found,
+                ));
+            };
+            edges[provider].push(dependent);
+            indegree[dependent] += 1;
+        }
+    }
+
+    let mut queue: VecDeque<usize> = (0..n).filter(|&i| indegree[i] == 0).collect();
+    let mut order = Vec::with_capacity(n);
+    while let Some(node) = queue.pop_front() {
+        order.push(node);
+        for &dependent in &edges[node] {
+            indegree[dependent] -= 1;
+            if indegree[dependent] == 0 {
+                queue.push_back(dependent);
+            }
+        }
+    }
+
+    if order.len() == n {
+        return Ok(order);
+    }
+
+    let residual: Vec<usize> = (0..n).filter(|&i| indegree[i] > 0).collect();
+    let cycle = find_cycle(&residual, &edges);
+    let chain = cycle
+        .into_iter()
+        .map(|idx| *constructors[idx].meta().type_info())
+        .collect();
+    Err(AppContextError::DependencyCycle { chain })
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Color {
+    White,
+    Gray,
+    Black,
+}
+
+/// DFS coloring (white/gray/black) over the residual subgraph left after the topological
+/// sweep, returning the node indices that make up one concrete cycle, in cycle order.
+fn find_cycle(residual: &[usize], edges: &[Vec<usize>]) -> Vec<usize> {
+    let mut color = vec![Color::White; edges.len()];
+    let mut stack = Vec::new();
+    let mut found = None;
+
+    for &start in residual {
+        if color[start] == Color::White {
+            visit(start, residual, edges, &mut color, &mut stack, &mut found);
+        }
+        if found.is_some() {
+            break;
+        }
+    }
+
+    found.unwrap_or_default()
+}
+
+fn visit(
+    node: usize,
+    residual: &[usize],
+    edges: &[Vec<usize>],
+    color: &mut [Color],
+    stack: &mut Vec<usize>,
+    found: &mut Option<Vec<usize>>,
+) {
+    color[node] = Color::Gray;
+    stack.push(node);
+
+    for &next in &edges[node] {
+        if found.is_some() {
+            break;
+        }
+        if !residual.contains(&next) {
+            continue;
+        }
+        match color[next] {
+            Color::White => visit(next, residual, edges, color, stack, found),
+            Color::Gray => {
+                let start = stack.iter().position(|&n| n == next).expect("back-edge target must be on the current DFS stack");
+                *found = Some(stack[start..].to_vec());
+            }
+            Color::Black => {}
+        }
+    }
+
+    if found.is_none() {
+        stack.pop();
+        color[node] = Color::Black;
+    }
+}
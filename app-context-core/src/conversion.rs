@@ -0,0 +1,119 @@
+use std::any::Any;
+use std::str::FromStr;
+
+use chrono::{DateTime, NaiveDateTime, Utc};
+
+use crate::error::{AppContextError, AppContextResult};
+
+/// how a raw `#[appobj(value = "...")]` string should be parsed into a field's runtime type
+#[derive(Debug, Clone, PartialEq)]
+pub enum Conversion {
+    Bytes,
+    Integer,
+    Float,
+    Boolean,
+    Timestamp,
+    TimestampFmt(String),
+    TimestampTZFmt(String),
+}
+
+impl FromStr for Conversion {
+    type Err = AppContextError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if let Some(fmt) = s.strip_prefix("timestamp+tz|") {
+            return Ok(Conversion::TimestampTZFmt(fmt.to_string()));
+        }
+        if let Some(fmt) = s.strip_prefix("timestamp|") {
+            return Ok(Conversion::TimestampFmt(fmt.to_string()));
+        }
+        match s {
+            "asis" | "bytes" | "string" => Ok(Conversion::Bytes),
+            "int" | "integer" => Ok(Conversion::Integer),
+            "float" => Ok(Conversion::Float),
+            "bool" | "boolean" => Ok(Conversion::Boolean),
+            "timestamp" => Ok(Conversion::Timestamp),
+            other => Err(AppContextError::ConversionFailed {
+                raw: other.to_string(),
+                target: "Conversion",
+                reason: "unrecognized conversion kind".to_string(),
+            }),
+        }
+    }
+}
+
+/// parses `$raw` as whichever of `$ty` matches `$target_type` (a `type_name::<T>()` string),
+/// boxing the result so the derive's `downcast::<#field_ty>()` lines up with the field's actual
+/// declared type instead of a conversion-chosen default.
+macro_rules! parse_as_target {
+    ($raw:expr, $target_type:expr, $($ty:ty),+ $(,)?) => {
+        match $target_type {
+            $(
+                stringify!($ty) => $raw
+                    .parse::<$ty>()
+                    .map(|v| Box::new(v) as Box<dyn Any>)
+                    .map_err(|e| AppContextError::ConversionFailed {
+                        raw: $raw.to_string(),
+                        target: stringify!($ty),
+                        reason: e.to_string(),
+                    }),
+            )+
+            other => Err(AppContextError::ConversionFailed {
+                raw: $raw.to_string(),
+                target: "numeric field",
+                reason: format!("unsupported field type `{other}` for this conversion"),
+            }),
+        }
+    };
+}
+
+impl Conversion {
+    /// `target_type` is the `type_name::<T>()` of the field the raw value is destined for, so
+    /// `Integer`/`Float` can box the exact integer/float width the field declares instead of
+    /// always producing `i64`/`f64`, which would fail the derive's later `downcast::<T>()` for
+    /// any other width (e.g. `u16`).
+    pub fn convert(&self, raw: &str, target_type: &'static str) -> AppContextResult<Box<dyn Any>> {
+        match self {
+            // "asis"/"bytes"/"string" all land here: box whichever of `String`/`Vec<u8>` the
+            // field actually declares, instead of always producing a `String` regardless of
+            // what the `bytes` alias implies.
+            Conversion::Bytes => Ok(match target_type {
+                "alloc::vec::Vec<u8>" => Box::new(raw.as_bytes().to_vec()) as Box<dyn Any>,
+                _ => Box::new(raw.to_string()) as Box<dyn Any>,
+            }),
+            Conversion::Integer => parse_as_target!(
+                raw, target_type, i8, i16, i32, i64, i128, isize, u8, u16, u32, u64, u128, usize
+            ),
+            Conversion::Float => parse_as_target!(raw, target_type, f32, f64),
+            Conversion::Boolean => raw
+                .parse::<bool>()
+                .map(|v| Box::new(v) as Box<dyn Any>)
+                .map_err(|e| AppContextError::ConversionFailed {
+                    raw: raw.to_string(),
+                    target: "bool",
+                    reason: e.to_string(),
+                }),
+            Conversion::Timestamp => DateTime::parse_from_rfc3339(raw)
+                .map(|v| Box::new(v.with_timezone(&Utc)) as Box<dyn Any>)
+                .map_err(|e| AppContextError::ConversionFailed {
+                    raw: raw.to_string(),
+                    target: "chrono::DateTime<Utc>",
+                    reason: e.to_string(),
+                }),
+            Conversion::TimestampFmt(fmt) => NaiveDateTime::parse_from_str(raw, fmt)
+                .map(|v| Box::new(v) as Box<dyn Any>)
+                .map_err(|e| AppContextError::ConversionFailed {
+                    raw: raw.to_string(),
+                    target: "chrono::NaiveDateTime",
+                    reason: e.to_string(),
+                }),
+            Conversion::TimestampTZFmt(fmt) => DateTime::parse_from_str(raw, fmt)
+                .map(|v| Box::new(v.with_timezone(&Utc)) as Box<dyn Any>)
+                .map_err(|e| AppContextError::ConversionFailed {
+                    raw: raw.to_string(),
+                    target: "chrono::DateTime<Utc>",
+                    reason: e.to_string(),
+                }),
+        }
+    }
+}
@@ -1,25 +1,142 @@
+use std::backtrace::{Backtrace, BacktraceStatus};
+
 use thiserror::Error;
 
+use crate::BaseInfo;
+
+fn render_chain(chain: &[BaseInfo]) -> String {
+    chain
+        .iter()
+        .map(|info| info.name())
+        .collect::<Vec<_>>()
+        .join(" -> ")
+}
+
+/// captures a backtrace at the call site, but only keeps it when `RUST_BACKTRACE` (or
+/// `RUST_LIB_BACKTRACE`) actually enabled capture, so the happy path stays cheap.
+fn capture_backtrace() -> Option<Backtrace> {
+    let backtrace = Backtrace::capture();
+    (backtrace.status() == BacktraceStatus::Captured).then_some(backtrace)
+}
+
 #[derive(Error, Debug)]
 pub enum AppContextError {
-    #[error("the app object `{obj_name}` with actual type `{obj_type}` cannot be casted to `{expected_type}")]
+    #[error("the app object `{obj_name}` with actual type `{obj_type}` cannot be casted to `{expected_type}`")]
     UnsupportedCast {
         obj_name: &'static str,
         obj_type: &'static str,
         expected_type: &'static str,
+        backtrace: Option<Backtrace>,
     },
 
     #[error("the app context is dropped")]
-    AppContextDropped,
+    AppContextDropped { backtrace: Option<Backtrace> },
 
     #[error("the app object `{obj_name}` with type `{obj_type}` is not found")]
     ObjectNotFound {
         obj_name: &'static str,
         obj_type: &'static str,
+        backtrace: Option<Backtrace>,
     },
 
     #[error("unexpected error `{0}`")]
     UnexpectedError(&'static str),
+
+    #[error("failed to convert value `{raw}` into `{target}`: {reason}")]
+    ConversionFailed {
+        raw: String,
+        target: &'static str,
+        reason: String,
+    },
+
+    #[error("dependency cycle detected: {}", render_chain(chain))]
+    DependencyCycle { chain: Vec<BaseInfo> },
+
+    #[error("{0}")]
+    Resolution(#[source] ResolutionChain),
+}
+
+impl AppContextError {
+    pub fn unsupported_cast(
+        obj_name: &'static str,
+        obj_type: &'static str,
+        expected_type: &'static str,
+    ) -> Self {
+        AppContextError::UnsupportedCast {
+            obj_name,
+            obj_type,
+            expected_type,
+            backtrace: capture_backtrace(),
+        }
+    }
+
+    pub fn app_context_dropped() -> Self {
+        AppContextError::AppContextDropped {
+            backtrace: capture_backtrace(),
+        }
+    }
+
+    pub fn object_not_found(obj_name: &'static str, obj_type: &'static str) -> Self {
+        AppContextError::ObjectNotFound {
+            obj_name,
+            obj_type,
+            backtrace: capture_backtrace(),
+        }
+    }
+
+    /// wraps `cause` with `node` recorded as a hop in its resolution chain, so a failure
+    /// surfaced while satisfying a nested dependency reports the full path that led to it
+    /// instead of just the leaf error. If `cause` is itself a `Resolution`, `node` is prepended
+    /// to its existing chain rather than wrapping it in another layer, so repeated calls up a
+    /// resolution stack flatten into one `A -> B -> C` chain instead of nested `Resolution`s.
+    pub fn resolution(node: BaseInfo, cause: AppContextError) -> Self {
+        match cause {
+            AppContextError::Resolution(chain) => AppContextError::Resolution(chain.prepend(node)),
+            other => AppContextError::Resolution(ResolutionChain::new(node, other)),
+        }
+    }
+}
+
+/// records the sequence of `BaseInfo`s visited while resolving a dependency, from the object
+/// that first needed something down to the one whose dependency couldn't be satisfied, with the
+/// leaf failure in parentheses, e.g. `A -> B -> (the app object \`C\` ... is not found)`.
+/// `Error::source` exposes `cause` so
+/// the whole trail is reachable through the standard source chain, not just `Display`.
+#[derive(Debug)]
+pub struct ResolutionChain {
+    path: Vec<BaseInfo>,
+    cause: Box<AppContextError>,
+}
+
+impl ResolutionChain {
+    fn new(node: BaseInfo, cause: AppContextError) -> Self {
+        ResolutionChain {
+            path: vec![node],
+            cause: Box::new(cause),
+        }
+    }
+
+    /// records that `node` was visited on the way to an already-built chain;
+    /// `AppContextError::resolution` calls this when the cause it wraps is already a `Resolution`.
+    pub fn prepend(mut self, node: BaseInfo) -> Self {
+        self.path.insert(0, node);
+        self
+    }
+}
+
+impl std::fmt::Display for ResolutionChain {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for node in &self.path {
+            write!(f, "{} -> ", node.name())?;
+        }
+        write!(f, "({})", self.cause)
+    }
+}
+
+impl std::error::Error for ResolutionChain {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        Some(self.cause.as_ref())
+    }
 }
 
 pub type AppContextResult<T> = Result<T, AppContextError>;
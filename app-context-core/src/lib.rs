@@ -1,17 +1,18 @@
+mod build_order;
+pub mod conversion;
 pub mod error;
-pub mod util;
 
 use std::{
     any::{type_name, Any},
     collections::HashMap,
     future::Future,
+    marker::PhantomData,
     ops::Deref,
     pin::Pin,
     sync::{Arc, LazyLock, RwLock, Weak},
 };
 
 use crate::error::{AppContextError, AppContextResult};
-use util::weak_to_ref;
 
 /// types define
 pub type DynBuilder = Arc<
@@ -28,6 +29,20 @@ pub struct BaseInfo {
     type_name: &'static str,
 }
 
+impl BaseInfo {
+    pub const fn new(name: &'static str, type_name: &'static str) -> Self {
+        BaseInfo { name, type_name }
+    }
+
+    pub(crate) fn name(&self) -> &'static str {
+        self.name
+    }
+
+    pub(crate) fn type_name(&self) -> &'static str {
+        self.type_name
+    }
+}
+
 #[derive(Debug, Clone, Copy)]
 pub struct ObjectMeta {
     type_info: BaseInfo,
@@ -36,7 +51,19 @@ pub struct ObjectMeta {
 }
 
 impl ObjectMeta {
-    fn compat_with(&self, expected: BaseInfo) -> bool {
+    pub const fn new(
+        type_info: BaseInfo,
+        deps: &'static [BaseInfo],
+        can_cast_to: &'static [BaseInfo],
+    ) -> Self {
+        ObjectMeta {
+            type_info,
+            deps,
+            can_cast_to,
+        }
+    }
+
+    pub(crate) fn compat_with(&self, expected: BaseInfo) -> bool {
         if self.type_info == expected {
             return true;
         }
@@ -49,20 +76,60 @@ impl ObjectMeta {
     fn depends_on(&self, expected: BaseInfo) -> bool {
         self.deps.contains(&expected)
     }
+
+    pub(crate) fn deps(&self) -> &'static [BaseInfo] {
+        self.deps
+    }
+
+    pub(crate) fn type_info(&self) -> &BaseInfo {
+        &self.type_info
+    }
 }
 
 pub trait AbstractAppObject: Any + Send + Sync + 'static {
-    fn try_cast_to(&self, type_name: &'static str) -> AppContextResult<Box<dyn Any + '_>>;
+    /// casts an owning `Arc<Self>` to the type named by `type_name`, handing back the result as
+    /// `Arc<T>` boxed behind `Any`. Taking `self` by `Arc` rather than `&self` means the boxed
+    /// value owns its data instead of borrowing it, so it stays `'static` and can round-trip
+    /// through `Any::downcast` without laundering a borrowed lifetime through it.
+    fn try_cast_to(self: Arc<Self>, type_name: &'static str) -> AppContextResult<Box<dyn Any>>;
 
     fn get_meta(&self) -> &'static ObjectMeta;
 }
 
+/// where an object's lifetime is anchored: for the whole process, or for a single
+/// `ScopedAppContext` (e.g. a request or session) named by `#[appobj(scope = "...")]`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ScopeKind {
+    #[default]
+    Singleton,
+    Scoped(&'static str),
+}
+
 /// constructor for derive macros
 pub struct ObjDynConstructor {
     meta: ObjectMeta,
+    scope: ScopeKind,
     builder: DynBuilder,
 }
 
+impl ObjDynConstructor {
+    pub fn new(meta: ObjectMeta, scope: ScopeKind, builder: DynBuilder) -> Self {
+        ObjDynConstructor {
+            meta,
+            scope,
+            builder,
+        }
+    }
+
+    pub(crate) fn meta(&self) -> &ObjectMeta {
+        &self.meta
+    }
+
+    pub(crate) fn scope(&self) -> ScopeKind {
+        self.scope
+    }
+}
+
 inventory::collect!(ObjDynConstructor);
 
 pub struct AppContextBuilder {
@@ -78,12 +145,19 @@ impl AppContextBuilder {
         }
     }
 
-    pub async fn collected() -> Self {
+    pub async fn collected() -> AppContextResult<Self> {
+        let constructors: Vec<&ObjDynConstructor> = inventory::iter::<ObjDynConstructor>()
+            .filter(|constructor| constructor.scope() == ScopeKind::Singleton)
+            .collect();
+        let build_order = build_order::resolve_build_order(&constructors)?;
+
         let mut builder = Self::new();
-        for constructor in inventory::iter::<ObjDynConstructor>() {
-            builder.register_dyn(constructor.builder.clone()).await;
+        for idx in build_order {
+            builder
+                .register_dyn(constructors[idx].builder.clone())
+                .await;
         }
-        builder
+        Ok(builder)
     }
 
     pub async fn register<Fut, T>(&mut self, builder: impl FnOnce(&AppContext) -> Fut)
@@ -111,15 +185,27 @@ pub struct AppContext {
 }
 
 impl AppContext {
-    pub fn get_obj<T: AbstractAppObject>(&self, expected: BaseInfo) -> Option<AppObjectRef<T>> {
+    pub fn get_obj<T: ?Sized + 'static>(&self, expected: BaseInfo) -> Option<AppObjectRef<T>> {
         self.inner
             .get_and_cache_inner(expected)
             .map(|weak| AppObjectRef::new(weak, expected))
     }
 
-    pub fn get_lazy_obj<T: AbstractAppObject>(&self, expected: BaseInfo) -> LazyAppObjectRef<T> {
+    pub fn get_lazy_obj<T: ?Sized + 'static>(&self, expected: BaseInfo) -> LazyAppObjectRef<T> {
         let inner = Arc::downgrade(&self.inner);
-        LazyAppObjectRef::lazy_new(inner, expected).unwrap()
+        LazyAppObjectRef::lazy_new(expected, move || {
+            let inner = inner.upgrade()?;
+            inner.get_and_cache_inner(expected)
+        })
+    }
+
+    /// opens a child context for request/session-lifetime objects: `get_obj`/`get_lazy_obj`
+    /// on the result search its own objects first and fall back to `self` for everything else.
+    pub fn enter_scope(&self) -> ScopedAppContext {
+        ScopedAppContext {
+            own: Arc::new(AppContextInner::default()),
+            parent: self.clone(),
+        }
     }
 }
 
@@ -171,10 +257,91 @@ impl AppContextInner {
     }
 }
 
+/// a child of an `AppContext` for objects scoped to a single request, session, or similar unit
+/// of work. Lookups check the scope's own objects first, then fall back to the parent chain.
+/// Dropping the last clone drops every object registered into the scope.
+#[derive(Clone)]
+pub struct ScopedAppContext {
+    own: Arc<AppContextInner>,
+    parent: AppContext,
+}
+
+impl ScopedAppContext {
+    pub fn get_obj<T: ?Sized + 'static>(&self, expected: BaseInfo) -> Option<AppObjectRef<T>> {
+        self.own
+            .get_and_cache_inner(expected)
+            .or_else(|| self.parent.inner.get_and_cache_inner(expected))
+            .map(|weak| AppObjectRef::new(weak, expected))
+    }
+
+    pub fn get_lazy_obj<T: ?Sized + 'static>(&self, expected: BaseInfo) -> LazyAppObjectRef<T> {
+        let own = Arc::downgrade(&self.own);
+        let parent = self.parent.clone();
+        LazyAppObjectRef::lazy_new(expected, move || {
+            if let Some(found) = own.upgrade().and_then(|own| own.get_and_cache_inner(expected)) {
+                return Some(found);
+            }
+            parent.inner.get_and_cache_inner(expected)
+        })
+    }
+}
+
+/// mirrors `AppContextBuilder`, but registers objects into a `ScopedAppContext` instead of the
+/// root `AppContext`. Builder closures still receive the parent `AppContext`, so scoped objects
+/// autowire against shared singletons rather than each other.
+pub struct ScopedAppContextBuilder {
+    inner: ScopedAppContext,
+}
+
+impl ScopedAppContextBuilder {
+    pub fn new(parent: &AppContext) -> Self {
+        ScopedAppContextBuilder {
+            inner: parent.enter_scope(),
+        }
+    }
+
+    /// builds one instance of every object registered with `#[appobj(scope = #scope_name)]`,
+    /// mirroring `AppContextBuilder::collected` but scanning inventory for `ScopeKind::Scoped`
+    /// entries matching `scope_name` instead of singletons. Scoped constructors only ever
+    /// autowire against `parent` (see `register`), so unlike singletons there's no inter-object
+    /// dependency ordering to resolve here.
+    pub async fn collected(parent: &AppContext, scope_name: &'static str) -> Self {
+        let mut builder = Self::new(parent);
+        for constructor in inventory::iter::<ObjDynConstructor>()
+            .filter(|constructor| constructor.scope() == ScopeKind::Scoped(scope_name))
+        {
+            builder.register_dyn(constructor.builder.clone()).await;
+        }
+        builder
+    }
+
+    pub async fn register<Fut, T>(&mut self, builder: impl FnOnce(&AppContext) -> Fut)
+    where
+        Fut: Future<Output = T>,
+        T: AbstractAppObject,
+    {
+        let val = builder(&self.inner.parent).await;
+        let mut_own =
+            Arc::get_mut(&mut self.inner.own).expect("builder inner is wrongly cloned");
+        mut_own.register_dyn(Arc::new(val));
+    }
+
+    pub async fn register_dyn(&mut self, dyn_builder: DynBuilder) {
+        let built = dyn_builder(&self.inner.parent).await;
+        let mut_own =
+            Arc::get_mut(&mut self.inner.own).expect("builder inner is wrongly cloned");
+        mut_own.register_dyn(built);
+    }
+
+    pub fn build(self) -> ScopedAppContext {
+        self.inner
+    }
+}
+
 pub struct AppObjectRef<T: ?Sized> {
     inner: Weak<dyn AbstractAppObject>,
     base_info: BaseInfo,
-    _marker: std::marker::PhantomData<T>,
+    _marker: PhantomData<T>,
 }
 
 impl<T> AppObjectRef<T>
@@ -185,23 +352,18 @@ where
         AppObjectRef {
             inner: arc,
             base_info,
-            _marker: std::marker::PhantomData,
+            _marker: PhantomData,
         }
     }
 
-    pub fn try_downcast(&self) -> AppContextResult<&T> {
-        cast_ref(&self.inner, self.base_info)
-    }
-}
-
-impl<T> Deref for AppObjectRef<T>
-where
-    T: ?Sized + 'static,
-{
-    type Target = T;
-
-    fn deref(&self) -> &Self::Target {
-        self.try_downcast().expect("downcast err")
+    /// upgrades the cached `Weak` into a strong guard, failing if the object has been dropped
+    /// or no longer casts to `T`.
+    pub fn get(&self) -> AppContextResult<AppObjectGuard<T>> {
+        let arc = self
+            .inner
+            .upgrade()
+            .ok_or_else(AppContextError::app_context_dropped)?;
+        AppObjectGuard::new(arc, self.base_info)
     }
 }
 
@@ -211,51 +373,89 @@ pub struct LazyAppObjectRef<T: ?Sized> {
         Box<dyn FnOnce() -> Option<Weak<dyn AbstractAppObject>>>,
     >,
     base_info: BaseInfo,
-    marker: std::marker::PhantomData<T>,
+    marker: PhantomData<T>,
 }
 
 impl<T> LazyAppObjectRef<T>
 where
     T: ?Sized + 'static,
 {
-    pub fn lazy_new(arc: Weak<AppContextInner>, base_info: BaseInfo) -> AppContextResult<Self> {
-        let init_f = move || {
-            let inner_ref = weak_to_ref(&arc)?;
-            let obj: Weak<dyn AbstractAppObject> = inner_ref.get_and_cache_inner(base_info)?;
-            Some(obj)
-        };
-        Ok(LazyAppObjectRef {
-            inner: LazyLock::new(Box::new(init_f)),
+    /// `resolver` is called at most once, the first time `get` is called, and
+    /// should look the object up (typically by upgrading some owning `Weak` just long enough
+    /// to search it, then dropping the strong ref again).
+    pub fn lazy_new(
+        base_info: BaseInfo,
+        resolver: impl FnOnce() -> Option<Weak<dyn AbstractAppObject>> + 'static,
+    ) -> Self {
+        LazyAppObjectRef {
+            inner: LazyLock::new(Box::new(resolver)),
             base_info,
-            marker: std::marker::PhantomData,
-        })
+            marker: PhantomData,
+        }
     }
 
-    pub fn try_downcast(&self) -> AppContextResult<&T> {
-        let weak_ptr = self.inner.as_ref().ok_or(AppContextError::ObjectNotFound {
-            obj_name: self.base_info.name,
-            obj_type: self.base_info.type_name,
+    /// upgrades the cached `Weak` into a strong guard, failing if the object has never resolved,
+    /// has been dropped, or no longer casts to `T`.
+    pub fn get(&self) -> AppContextResult<AppObjectGuard<T>> {
+        let weak_ptr = self.inner.as_ref().ok_or_else(|| {
+            AppContextError::object_not_found(self.base_info.name(), self.base_info.type_name())
         })?;
-        cast_ref(weak_ptr, self.base_info)
+        let arc = weak_ptr
+            .upgrade()
+            .ok_or_else(AppContextError::app_context_dropped)?;
+        AppObjectGuard::new(arc, self.base_info)
+    }
+}
+
+/// owns a strongly-typed `Arc<T>` cast from a resolved app object, so the `Deref` it hands out
+/// is always backed by a live value without re-casting on every access.
+pub struct AppObjectGuard<T: ?Sized> {
+    typed: Arc<T>,
+}
+
+impl<T> AppObjectGuard<T>
+where
+    T: ?Sized + 'static,
+{
+    fn new(arc: Arc<dyn AbstractAppObject>, base_info: BaseInfo) -> AppContextResult<Self> {
+        let typed = cast_any_arc::<T>(arc, base_info)?;
+        Ok(AppObjectGuard { typed })
+    }
+}
+
+impl<T> Deref for AppObjectGuard<T>
+where
+    T: ?Sized + 'static,
+{
+    type Target = T;
+
+    fn deref(&self) -> &Self::Target {
+        &self.typed
     }
 }
 
-fn cast_ref<T: ?Sized + 'static>(
-    weak_ptr: &Weak<dyn AbstractAppObject>,
+/// casts `arc` into a strongly-typed `Arc<T>`, wrapping any failure in a `ResolutionChain`
+/// rooted at `base_info`. The chain holds only this one hop: the cast error it wraps never
+/// carries a chain of its own, so failures of nested autowired fields are not added to it.
+/// Casting through an owning `Arc<T>` (rather than a borrowed `&T`) keeps
+/// the round-trip through `Any::downcast` sound, since `Arc<T>: 'static` whenever `T: 'static`.
+fn cast_any_arc<T: ?Sized + 'static>(
+    arc: Arc<dyn AbstractAppObject>,
     base_info: BaseInfo,
-) -> AppContextResult<&T> {
-    let Some(r_ref) = weak_to_ref(weak_ptr) else {
-        return Err(AppContextError::AppContextDropped);
-    };
-    let cast_any = r_ref.try_cast_to(type_name::<T>())?;
-    let actual_ref = cast_any
-        .downcast::<&T>()
-        .map_err(|_| AppContextError::UnsupportedCast {
-            obj_name: base_info.name,
-            obj_type: base_info.type_name,
-            expected_type: type_name::<T>(),
-        })?;
-    Ok(*actual_ref)
+) -> AppContextResult<Arc<T>> {
+    let expected_type = type_name::<T>();
+    let cast_any = arc
+        .try_cast_to(expected_type)
+        .map_err(|e| AppContextError::resolution(base_info, e))?;
+    let typed = cast_any.downcast::<Arc<T>>().map_err(|_| {
+        let unsupported = AppContextError::unsupported_cast(
+            base_info.name(),
+            base_info.type_name(),
+            expected_type,
+        );
+        AppContextError::resolution(base_info, unsupported)
+    })?;
+    Ok(*typed)
 }
 
 #[cfg(test)]
@@ -1,41 +1,332 @@
 use proc_macro::TokenStream;
-use quote::quote;
-use syn::parse_macro_input;
-use syn::DeriveInput;
+use quote::{format_ident, quote};
+use syn::punctuated::Punctuated;
+use syn::{
+    parse_macro_input, Data, DeriveInput, Expr, ExprLit, Fields, GenericArgument, Lit, Meta,
+    PathArguments, Token, Type,
+};
 
+enum InjectInfo {
+    AutoWire { obj_name: String },
+    Value { env_name: String, conversion: String },
+}
 
-enum InjectInfo{
-    AutoWire{
-        obj_name:String
-    },
-    Value{
-        env_name:String
-    }
+struct FieldInfo {
+    name: String,
+    ty: String,
+    inject_info: InjectInfo,
+}
+
+struct ExportInfo {
+    export_as: Vec<String>,
+    export_name: String,
 }
 
-struct FieldInfo{
-    name:String,
-    ty:String,
-    inject_info:InjectInfo
+struct StructInfo {
+    name: String,
+    export_info: ExportInfo,
+    fields: Vec<FieldInfo>,
 }
 
-struct ExportInfo{
-    export_as:Vec<String>,
-    export_name:String
+/// collects the `key="value"` pairs out of every `#[appobj(...)]` attribute attached to `attrs`
+fn appobj_pairs(attrs: &[syn::Attribute]) -> Vec<(String, String)> {
+    let mut pairs = Vec::new();
+    for attr in attrs {
+        if !attr.path().is_ident("appobj") {
+            continue;
+        }
+        let nested = attr
+            .parse_args_with(Punctuated::<Meta, Token![,]>::parse_terminated)
+            .expect("malformed #[appobj(...)] attribute");
+        for meta in nested {
+            let Meta::NameValue(nv) = meta else {
+                panic!("#[appobj(...)] only supports `key = \"value\"` entries");
+            };
+            let key = nv
+                .path
+                .get_ident()
+                .expect("#[appobj(...)] keys must be plain identifiers")
+                .to_string();
+            let Expr::Lit(ExprLit {
+                lit: Lit::Str(value),
+                ..
+            }) = nv.value
+            else {
+                panic!("#[appobj({key} = ...)] value must be a string literal");
+            };
+            pairs.push((key, value.value()));
+        }
+    }
+    pairs
 }
 
-struct StructInfo{
-    name:String,
-    export_info:ExportInfo,
-    fields:Vec<FieldInfo>
+/// if `ty` is `LazyAppObjectRef<Inner>`, returns `Inner`
+fn lazy_ref_inner(ty: &Type) -> Option<&Type> {
+    let Type::Path(type_path) = ty else {
+        return None;
+    };
+    let segment = type_path.path.segments.last()?;
+    if segment.ident != "LazyAppObjectRef" {
+        return None;
+    }
+    let PathArguments::AngleBracketed(args) = &segment.arguments else {
+        return None;
+    };
+    args.args.iter().find_map(|arg| match arg {
+        GenericArgument::Type(inner) => Some(inner),
+        _ => None,
+    })
 }
 
+fn parse_struct_info(input: &DeriveInput) -> (StructInfo, Vec<syn::Path>, Option<String>) {
+    let name = input.ident.to_string();
+
+    let struct_pairs = appobj_pairs(&input.attrs);
+    let export_as = struct_pairs
+        .iter()
+        .find(|(k, _)| k == "export_as")
+        .map(|(_, v)| {
+            v.split(',')
+                .map(|s| s.trim().to_string())
+                .filter(|s| !s.is_empty())
+                .collect()
+        })
+        .unwrap_or_default();
+    let scope = struct_pairs
+        .iter()
+        .find(|(k, _)| k == "scope")
+        .map(|(_, v)| v.clone());
+
+    let export_paths: Vec<syn::Path> = export_as
+        .iter()
+        .map(|s| syn::parse_str(s).unwrap_or_else(|_| panic!("`{s}` is not a valid trait path")))
+        .collect();
+
+    let Data::Struct(data) = &input.data else {
+        panic!("#[derive(AppObj)] only supports structs");
+    };
+    let Fields::Named(named) = &data.fields else {
+        panic!("#[derive(AppObj)] only supports structs with named fields");
+    };
+
+    let fields = named
+        .named
+        .iter()
+        .map(|field| {
+            let field_name = field.ident.as_ref().unwrap().to_string();
+            let field_ty = &field.ty;
+            let ty_string = quote!(#field_ty).to_string();
+            let field_pairs = appobj_pairs(&field.attrs);
+
+            let inject_info = if let Some((_, obj_name)) =
+                field_pairs.iter().find(|(k, _)| k == "autowire")
+            {
+                InjectInfo::AutoWire {
+                    obj_name: obj_name.clone(),
+                }
+            } else if let Some((_, env_name)) = field_pairs.iter().find(|(k, _)| k == "value") {
+                let conversion = field_pairs
+                    .iter()
+                    .find(|(k, _)| k == "as")
+                    .map(|(_, v)| v.clone())
+                    .unwrap_or_else(|| "asis".to_string());
+                InjectInfo::Value {
+                    env_name: env_name.clone(),
+                    conversion,
+                }
+            } else {
+                panic!(
+                    "field `{field_name}` needs either #[appobj(autowire=\"...\")] or #[appobj(value=\"...\")]"
+                );
+            };
 
+            FieldInfo {
+                name: field_name,
+                ty: ty_string,
+                inject_info,
+            }
+        })
+        .collect();
+
+    (
+        StructInfo {
+            name,
+            export_info: ExportInfo {
+                export_as,
+                export_name: input.ident.to_string(),
+            },
+            fields,
+        },
+        export_paths,
+        scope,
+    )
+}
 
 #[proc_macro_derive(AppObj, attributes(appobj))]
 pub fn derive_serialize(input: TokenStream) -> TokenStream {
     let parsed = parse_macro_input!(input as DeriveInput);
-    println!("res is {:#?}",parsed);
+    let struct_ident = parsed.ident.clone();
+    let (struct_info, export_paths, scope) = parse_struct_info(&parsed);
+
+    let export_name = &struct_info.export_info.export_name;
+    let scope_kind = match scope {
+        Some(name) => quote! { ::app_context_core::ScopeKind::Scoped(#name) },
+        None => quote! { ::app_context_core::ScopeKind::Singleton },
+    };
+
+    let mut dep_entries = Vec::new();
+    let mut field_names = Vec::new();
+    let mut field_inits = Vec::new();
+
+    for field in &struct_info.fields {
+        let field_ident = format_ident!("{}", field.name);
+        let field_ty: Type = syn::parse_str(&field.ty).expect("failed to re-parse field type");
+        field_names.push(field_ident.clone());
+
+        match &field.inject_info {
+            InjectInfo::AutoWire { obj_name } => {
+                if let Some(inner_ty) = lazy_ref_inner(&field_ty) {
+                    dep_entries.push(quote! {
+                        ::app_context_core::BaseInfo::new(#obj_name, ::std::any::type_name::<#inner_ty>())
+                    });
+                    field_inits.push(quote! {
+                        let #field_ident: #field_ty = ctx.get_lazy_obj::<#inner_ty>(
+                            ::app_context_core::BaseInfo::new(#obj_name, ::std::any::type_name::<#inner_ty>())
+                        );
+                    });
+                } else {
+                    dep_entries.push(quote! {
+                        ::app_context_core::BaseInfo::new(#obj_name, ::std::any::type_name::<#field_ty>())
+                    });
+                    field_inits.push(quote! {
+                        let #field_ident: #field_ty = {
+                            let dep_ref = ctx
+                                .get_obj::<#field_ty>(::app_context_core::BaseInfo::new(
+                                    #obj_name,
+                                    ::std::any::type_name::<#field_ty>(),
+                                ))
+                                .unwrap_or_else(|| {
+                                    panic!(
+                                        "autowire failed: no object named `{}` of type `{}` is registered",
+                                        #obj_name,
+                                        ::std::any::type_name::<#field_ty>(),
+                                    )
+                                });
+                            let guard = dep_ref
+                                .get()
+                                .unwrap_or_else(|e| panic!("autowire failed for `{}`: {e}", #obj_name));
+                            ::std::clone::Clone::clone(&*guard)
+                        };
+                    });
+                }
+            }
+            InjectInfo::Value {
+                env_name,
+                conversion,
+            } => {
+                field_inits.push(quote! {
+                    let #field_ident: #field_ty = {
+                        let raw = ::std::env::var(#env_name).unwrap_or_else(|_| {
+                            panic!("missing required environment variable `{}`", #env_name)
+                        });
+                        let conversion =
+                            <::app_context_core::conversion::Conversion as ::std::str::FromStr>::from_str(#conversion)
+                                .unwrap_or_else(|e| {
+                                    panic!("invalid #[appobj(value = \"{}\", as = \"{}\")] conversion: {}", #env_name, #conversion, e)
+                                });
+                        let converted = conversion
+                            .convert(&raw, ::std::any::type_name::<#field_ty>())
+                            .unwrap_or_else(|e| panic!("{e}"));
+                        *converted.downcast::<#field_ty>().unwrap_or_else(|_| {
+                            panic!(
+                                "environment variable `{}` could not be converted into `{}`",
+                                #env_name,
+                                ::std::any::type_name::<#field_ty>(),
+                            )
+                        })
+                    };
+                });
+            }
+        }
+    }
+
+    let try_cast_arms = export_paths.iter().map(|path| {
+        quote! {
+            if type_name == ::std::any::type_name::<dyn #path>() {
+                return ::std::result::Result::Ok(::std::boxed::Box::new(self as ::std::sync::Arc<dyn #path>));
+            }
+        }
+    });
+
+    let expanded = quote! {
+        const _: () = {
+            // `type_name` isn't a stable `const fn`, so `ObjectMeta` can't be assembled in a
+            // `const`/`static` initializer; build it lazily on first access instead, and leak
+            // its dependency/cast-target lists to get the `&'static [BaseInfo]` slices it wants.
+            static OBJECT_META: ::std::sync::LazyLock<::app_context_core::ObjectMeta> =
+                ::std::sync::LazyLock::new(|| {
+                    let deps: &'static [::app_context_core::BaseInfo] =
+                        ::std::boxed::Box::leak(::std::vec![#(#dep_entries),*].into_boxed_slice());
+                    let can_cast_to: &'static [::app_context_core::BaseInfo] =
+                        ::std::boxed::Box::leak(::std::vec![
+                            #(::app_context_core::BaseInfo::new(
+                                #export_name,
+                                ::std::any::type_name::<dyn #export_paths>(),
+                            )),*
+                        ].into_boxed_slice());
+                    ::app_context_core::ObjectMeta::new(
+                        ::app_context_core::BaseInfo::new(#export_name, ::std::any::type_name::<#struct_ident>()),
+                        deps,
+                        can_cast_to,
+                    )
+                });
+
+            impl ::app_context_core::AbstractAppObject for #struct_ident {
+                fn try_cast_to(
+                    self: ::std::sync::Arc<Self>,
+                    type_name: &'static str,
+                ) -> ::app_context_core::error::AppContextResult<::std::boxed::Box<dyn ::std::any::Any>> {
+                    if type_name == ::std::any::type_name::<#struct_ident>() {
+                        return ::std::result::Result::Ok(::std::boxed::Box::new(self));
+                    }
+                    #(#try_cast_arms)*
+                    ::std::result::Result::Err(::app_context_core::error::AppContextError::unsupported_cast(
+                        #export_name,
+                        ::std::any::type_name::<#struct_ident>(),
+                        type_name,
+                    ))
+                }
+
+                fn get_meta(&self) -> &'static ::app_context_core::ObjectMeta {
+                    &OBJECT_META
+                }
+            }
 
-    TokenStream::from(quote! {})
+            ::inventory::submit! {
+                ::app_context_core::ObjDynConstructor::new(
+                    *OBJECT_META,
+                    #scope_kind,
+                    {
+                        let builder: ::app_context_core::DynBuilder = ::std::sync::Arc::new(
+                            move |ctx: &::app_context_core::AppContext| {
+                                let ctx = ctx.clone();
+                                ::std::boxed::Box::pin(async move {
+                                    #(#field_inits)*
+                                    let obj = #struct_ident {
+                                        #(#field_names),*
+                                    };
+                                    ::std::sync::Arc::new(obj) as ::std::sync::Arc<dyn ::app_context_core::AbstractAppObject>
+                                })
+                                    as ::std::pin::Pin<::std::boxed::Box<
+                                        dyn ::std::future::Future<Output = ::std::sync::Arc<dyn ::app_context_core::AbstractAppObject>>,
+                                    >>
+                            },
+                        );
+                        builder
+                    },
+                )
+            }
+        };
+    };
+    TokenStream::from(expanded)
 }
@@ -4,7 +4,6 @@ use app_context_derive::AppObj;
 
 
 #[derive(AppObj)]
-#[appobj(export_as="Read,Write")]
 pub struct MyStruct{
     #[appobj(autowire="someName")]
     name:String,